@@ -40,211 +40,34 @@ const kModes = [
   },
 ];
 
-const kKnownMetrics = {
-  average_temperature: {
-    pretty: "Average temperature",
-    unit: "Celsius",
-  },
-  average_max_temperature: {
-    pretty: "Average max temperature",
-    unit: "Celsius",
-  },
-  average_min_temperature: {
-    pretty: "Average min temperature",
-    unit: "Celsius",
-  },
-  absolute_max_temperature: {
-    pretty: "Absolute max temperature",
-    unit: "Celsius",
-    with_date: true,
-  },
-  absolute_min_temperature: {
-    pretty: "Absolute min temperature",
-    unit: "Celsius",
-    with_date: true,
-  },
-  higher_min_temperature: {
-    pretty: "Higher min temperature",
-    unit: "Celsius",
-  },
-  lower_max_temperature: {
-    pretty: "Lower max temperature",
-    unit: "Celsius",
-  },
-  number_of_days_gteq_30_celsius: {
-    pretty: "Number of days with >= 30 degrees celsius",
-    unit: "Days",
-  },
-  number_of_days_lteq_0_celsius: {
-    pretty: "Number of days with <= 0 degrees celsius",
-    unit: "Days",
-  },
-  total_rain: {
-    pretty: "Total rain",
-    unit: "Mm",
-  },
-  max_rain: {
-    pretty: "Max rain",
-    unit: "Mm",
-    with_date: true,
-  },
-  days_with_appreciable_rain: {
-    pretty: "Days with appreciable rain (>= 0.1mm)",
-    unit: "Days",
-    multiplier: 0.1,
-  },
-  days_with_rain_gteq_1_mm: {
-    pretty: "Days with >= 1mm of rain",
-    unit: "Days",
-  },
-  days_with_rain_gteq_10_mm: {
-    pretty: "Days with >= 10mm of rain",
-    unit: "Days",
-  },
-  days_with_rain_gteq_30_mm: {
-    pretty: "Days with >= 30mm of rain",
-    unit: "Days",
-  },
-  average_relative_humidity: {
-    pretty: "Average relative humidity",
-    unit: "%",
-  },
-  average_vapor_tension: {
-    pretty: "Average vapor tension",
-    unit: "hPa",
-    multiplier: 0.1,
-  },
-  days_of_rain: {
-    pretty: "Days of rain",
-    unit: "Days",
-  },
-  days_of_snow: {
-    pretty: "Days of snow",
-    unit: "Days",
-  },
-  days_of_hail: {
-    pretty: "Days of hail",
-    unit: "Days",
-  },
-  days_of_storm: {
-    pretty: "Days of storm",
-    unit: "Days",
-  },
-  days_of_fog: {
-    pretty: "Days of fog",
-    unit: "Days",
-  },
-  clear_days: {
-    pretty: "Clear days",
-    unit: "Days",
-  },
-  cloudy_days: {
-    pretty: "Cloudy days",
-    unit: "Days",
-  },
-  covered_days: {
-    pretty: "Covered days",
-    unit: "Days",
-  },
-  hours_of_sun: {
-    pretty: "Hours of sun",
-    unit: "Hours",
-  },
-  average_percentage_against_theoric_insolation: {
-    pretty: "Average percentage against theoric insolation",
-    unit: "%",
-  },
-  evaporation: {
-    pretty: "Evaporation",
-    unit: "Mm",
-    multiplier: 0.1,
-  },
-  average_distance: {
-    pretty: "Average distance",
-    unit: "Km",
-  },
-  days_with_wind_greater_than_55_km_per_hour: {
-    pretty: "Days with wind > 55km/h",
-    unit: "Days",
-  },
-  days_with_wind_greater_than_91_km_per_hour: {
-    pretty: "Days with wind > 91km/h",
-    unit: "Days",
-  },
-  average_wind_speed: {
-    pretty: "Average wind speed",
-    unit: "km/h",
-  },
-  average_pressure: {
-    pretty: "Average pressure",
-    unit: "hPa",
-    multiplier: 0.1,
-  },
-  average_pressure_sea_level: {
-    pretty: "Average pressure at sea level",
-    unit: "hPa",
-    multiplier: 0.1,
-  },
-  max_pressure: {
-    pretty: "Max pressure",
-    unit: "hPa",
-    multiplier: 0.1,
-    with_date: true,
-  },
-  min_pressure: {
-    pretty: "Min pressure",
-    unit: "hPa",
-    multiplier: 0.1,
-    with_date: true,
-  },
-  average_temperature_under_10_cm: {
-    pretty: "Average temperature under 10 cm",
-    unit: "Celsius",
-    multiplier: 0.1,
-  },
-  average_temperature_under_20_cm: {
-    pretty: "Average temperature under 20 cm",
-    unit: "Celsius",
-    multiplier: 0.1,
-  },
-  average_temperature_under_50_cm: {
-    pretty: "Average temperature under 50 cm",
-    unit: "Celsius",
-    multiplier: 0.1,
-  },
-  days_with_visibility_lt_50_m: {
-    pretty: "Days with visibility < 50m",
-    unit: "Days",
-  },
-  days_with_visibility_gteq_50_m_lt_100_m: {
-    pretty: "Days with visibility < 50m",
-    unit: "Days",
-  },
-  days_with_visibility_gteq_100_m_lt_1000_m: {
-    pretty: "Days with visibility >= 100m < 1000m",
-    unit: "Days",
-  },
-};
-
-const kKnownUnits = (function() {
-  const units = {};
-  for (const metric in kKnownMetrics) {
-    const m = kKnownMetrics[metric];
-    if (!units[m.unit])
-      units[m.unit] = [];
-    units[m.unit].push(metric);
+// The metrics we know about, and the metrics for each unit, populated from the
+// variable registry in the schema, derived variables included.
+const kKnownMetrics = {};
+const kKnownUnits = {};
+
+function setupKnownMetrics(variables) {
+  for (const variable of variables) {
+    kKnownMetrics[variable.name] = {
+      pretty: variable.description_en,
+      unit: variable.unit,
+      multiplier: variable.scale,
+      with_date: variable.with_date,
+    };
+    if (!kKnownUnits[variable.unit])
+      kKnownUnits[variable.unit] = [];
+    kKnownUnits[variable.unit].push(variable.name);
   }
-  return units;
-})();
+}
 
 const kDefaultDotRadius = 2.5;
 const kDefaultLineThickness = 1;
 
 window.Charts = class Charts {
-  constructor(chartContainer, controls, schema, afterFrameCallback) {
+  constructor(chartContainer, controls, schema, stations, afterFrameCallback) {
     this.chartContainer = chartContainer;
     this.controls = controls;
-    this.schema = schema;
+    this.schema = schema.datasets;
+    setupKnownMetrics(schema.variables.concat(schema.derived || []));
     this.data = {};
     this.loadingData = {};
     this.width = 1200;
@@ -262,7 +85,7 @@ window.Charts = class Charts {
     this.scheduledFrameUpdates = new Set();
     this.combinedChartNeedsRebuild = false;
     this.animationFrame = 0;
-    this.setupStations();
+    this.setupStations(stations);
     this.buildControlsAndCharts();
     this.scheduleRebuildAllCharts();
   }
@@ -329,10 +152,9 @@ window.Charts = class Charts {
     });
   }
 
-  setupStations() {
-    for (const data of this.schema)
-      for (const station of data.stations)
-        this.stations[station.id] = station;
+  setupStations(stations) {
+    for (const station of stations)
+      this.stations[station.id] = station;
   }
 
   checkboxChanged(input) {
@@ -444,7 +266,7 @@ window.Charts = class Charts {
         input.addEventListener("change", e => this.selectedStationChanged(e.target));
         label.classList.add("checked");
         label.appendChild(input);
-        label.appendChild(document.createTextNode(`${station.id} - ${station.name} (${station.city}, ${station.province})`));
+        label.appendChild(document.createTextNode(`${station.id} - ${station.display_name || station.name} (${station.city}, ${station.province})`));
         label.title = `${station.altitude} - ${station.latitude} - ${station.longitude}`;
         stationControls.appendChild(label);
       }
@@ -849,13 +671,15 @@ window.Charts = class Charts {
             let value = kKnownMetrics[m].with_date ? longValue.value : longValue;
             if (kKnownMetrics[m].multiplier)
               value *= kKnownMetrics[m].multiplier;
-            let key = `${kKnownMetrics[m].pretty} - ${this.stations[station].name}`;
+            let key = `${kKnownMetrics[m].pretty} - ${this.stations[station].display_name || this.stations[station].name}`;
             if (overlayYears)
               key += ` - ${data.year}`;
 
             min = Math.min(min, value);
             max = Math.max(max, value);
 
+            // "high" or "low" if the value is beyond the 1981-2010 normals.
+            const records = data.records && data.records[m] && data.records[m][station];
             if (!lines[key])
               lines[key] = [];
             lines[key].push({
@@ -863,6 +687,7 @@ window.Charts = class Charts {
               month: currentMonth,
               value,
               date: longValue.date,
+              record: records ? records[month] : undefined,
             });
           }
         }
@@ -1125,6 +950,13 @@ window.Charts = class Charts {
           let title = `${lineKey} - ${kMonths[point.month]} - ${point.value}`;
           if (point.date)
             title += ` - (date: ${point.date})`;
+          if (point.record) {
+            circle.classList.add("record");
+            circle.setAttribute("stroke", point.record == "high" ? "red" : "blue");
+            circle.setAttribute("stroke-width", lineThickness);
+            circle.setAttribute("r", dotRadius * 2);
+            title += ` - (record ${point.record})`;
+          }
           circle.setAttribute("title", title);
           pointsAttr.push(`${x},${y}`);
           pointContainer.appendChild(circle);